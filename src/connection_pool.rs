@@ -0,0 +1,146 @@
+//! 每条连接参数（命名空间+可选的远程主机/凭据）一个专属的后台COM线程，供`queryAsync`分发查询。
+//!
+//! `WMIConnection`不是`Send`的：它必须在创建它的线程上使用。
+//! 为了让`query`在不阻塞Node事件循环的情况下并发执行，本模块为每组连接参数懒加载
+//! 一个长期存活的工作线程（类似守护进程），查询请求通过channel发给它，线程拥有
+//! 自己的连接并把结果送回来，而不是每次查询都重新创建连接或把连接跨线程共享。
+//!
+//! 连接池按完整的[`ConnectionParams`]（而不是只按命名空间字符串）分桶：两个指向
+//! 不同远程主机、但命名空间相同（如都使用`root/cimv2`）的客户端必须各自拥有独立的
+//! 后台连接，否则会悄悄查到错误主机的数据。
+
+use crate::{build_wmi_connection, rows_to_json, ConnectionParams, ConversionOptions};
+use napi::bindgen_prelude::*;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use wmi::{Variant, WMIConnection};
+
+struct QueryRequest {
+    wql: String,
+    conversion: ConversionOptions,
+    responder: Sender<std::result::Result<String, String>>,
+}
+
+/// 持有一组连接参数专属后台线程的句柄，查询请求通过channel分派给它
+struct ConnectionController {
+    sender: Sender<QueryRequest>,
+}
+
+impl ConnectionController {
+    fn spawn(params: ConnectionParams) -> Result<Self> {
+        let (request_tx, request_rx) = mpsc::channel::<QueryRequest>();
+        let (ready_tx, ready_rx) = mpsc::channel::<std::result::Result<(), String>>();
+
+        thread::spawn(move || {
+            let mut wmi_con = match connect(&params) {
+                Ok(con) => con,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            let _ = ready_tx.send(Ok(()));
+
+            while let Ok(request) = request_rx.recv() {
+                let mut result = run_query(&wmi_con, &request.wql, &request.conversion);
+
+                // 连接可能已经失效（例如远程主机重启），而不仅仅是这条WQL写错了。
+                // 与其让这个命名空间/主机从此永久失败，不如重建一次连接再重试一次；
+                // 这与subscribe()的重连模型一致。重连也失败的话就把原始查询错误还给调用方。
+                if result.is_err() {
+                    if let Ok(reconnected) = connect(&params) {
+                        wmi_con = reconnected;
+                        result = run_query(&wmi_con, &request.wql, &request.conversion);
+                    }
+                }
+
+                let _ = request.responder.send(result);
+            }
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| Error::new(Status::GenericFailure, "连接线程异常退出".to_string()))?
+            .map_err(|e| Error::new(Status::GenericFailure, e))?;
+
+        Ok(ConnectionController { sender: request_tx })
+    }
+
+    fn query(&self, wql: String, conversion: ConversionOptions) -> Result<String> {
+        let (response_tx, response_rx) = mpsc::channel();
+
+        self.sender
+            .send(QueryRequest {
+                wql,
+                conversion,
+                responder: response_tx,
+            })
+            .map_err(|_| Error::new(Status::GenericFailure, "连接线程已退出".to_string()))?;
+
+        response_rx
+            .recv()
+            .map_err(|_| Error::new(Status::GenericFailure, "连接线程未返回结果".to_string()))?
+            .map_err(|e| Error::new(Status::GenericFailure, e))
+    }
+}
+
+fn connect(params: &ConnectionParams) -> std::result::Result<WMIConnection, String> {
+    build_wmi_connection(params)
+}
+
+fn run_query(
+    wmi_con: &WMIConnection,
+    wql: &str,
+    conversion: &ConversionOptions,
+) -> std::result::Result<String, String> {
+    let results: Vec<HashMap<String, Variant>> = wmi_con
+        .raw_query(wql)
+        .map_err(|e| format!("查询失败: {}", e))?;
+
+    serde_json::to_string(&rows_to_json(results, conversion))
+        .map_err(|e| format!("JSON序列化失败: {}", e))
+}
+
+fn registry() -> &'static Mutex<HashMap<ConnectionParams, Arc<ConnectionController>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<ConnectionParams, Arc<ConnectionController>>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn controller_for(params: &ConnectionParams) -> Result<Arc<ConnectionController>> {
+    let mut controllers = registry()
+        .lock()
+        .map_err(|_| Error::new(Status::GenericFailure, "连接注册表已损坏".to_string()))?;
+
+    if let Some(existing) = controllers.get(params) {
+        return Ok(existing.clone());
+    }
+
+    let controller = Arc::new(ConnectionController::spawn(params.clone())?);
+    controllers.insert(params.clone(), controller.clone());
+    Ok(controller)
+}
+
+/// napi `Task`，在连接参数专属的后台线程上执行一次WQL查询并把JSON结果带回JS的Promise
+pub struct QueryTask {
+    pub params: ConnectionParams,
+    pub wql: String,
+    pub conversion: ConversionOptions,
+}
+
+impl Task for QueryTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let controller = controller_for(&self.params)?;
+        controller.query(self.wql.clone(), self.conversion)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
@@ -1,68 +1,315 @@
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
 use serde_json::Value;
 use std::collections::HashMap;
-use wmi::{COMLibrary, WMIConnection, Variant};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use wmi::{IWbemClassWrapper, Variant, WMIConnection};
+
+mod connection_pool;
+
+// JS Number的安全整数范围，超出此范围的I8/UI8在bigint_for_i8开启时改为JSON字符串
+const MAX_SAFE_INTEGER: i64 = 9_007_199_254_740_991;
+const MIN_SAFE_INTEGER: i64 = -9_007_199_254_740_991;
+
+// 订阅管理循环检查`stopped`标志的节奏，决定stop()之后最多等待多久才真正退出
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 事件订阅的连接出错后，重连前的固定退避时间，避免WMI主机长时间下线时疯狂重试
+const SUBSCRIPTION_RECONNECT_BACKOFF: Duration = Duration::from_secs(2);
+
+// 控制variant_to_json的转换行为，对应WmiClientConfig里暴露给JS的开关
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ConversionOptions {
+    pub parse_datetimes: bool,
+    pub bigint_for_i8: bool,
+}
+
+// 将CIM_DATETIME格式（yyyymmddHHMMSS.ffffff+UUU，UUU为与UTC的分钟偏移，*表示偏移未知）
+// 解析为ISO-8601字符串；不匹配该格式的字符串原样返回None，由调用方保持原值
+fn parse_cim_datetime(s: &str) -> Option<String> {
+    // 任意WMI字符串属性都会走到这里，不只是真正的日期时间字段；必须先确认整串是ASCII，
+    // 否则下面按固定字节偏移切片在多字节字符上会越过字符边界导致panic
+    if s.len() != 25 || !s.is_ascii() || s.as_bytes()[14] != b'.' {
+        return None;
+    }
+
+    let all_digits = |part: &str| part.len() > 0 && part.bytes().all(|b| b.is_ascii_digit());
+
+    let year = &s[0..4];
+    let month = &s[4..6];
+    let day = &s[6..8];
+    let hour = &s[8..10];
+    let minute = &s[10..12];
+    let second = &s[12..14];
+    let micros = &s[15..21];
+    let sign = &s[21..22];
+    let offset = &s[22..25];
+
+    if ![year, month, day, hour, minute, second, micros, offset]
+        .iter()
+        .all(|part| all_digits(part))
+    {
+        return None;
+    }
+
+    let timestamp = format!("{year}-{month}-{day}T{hour}:{minute}:{second}.{micros}");
+
+    match sign {
+        "*" => Some(timestamp),
+        "+" | "-" => {
+            let offset_minutes: i32 = offset.parse().ok()?;
+            Some(format!(
+                "{timestamp}{sign}{:02}:{:02}",
+                offset_minutes / 60,
+                offset_minutes % 60
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn i8_to_json(i: i64, options: &ConversionOptions) -> Value {
+    if options.bigint_for_i8 && !(MIN_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&i) {
+        Value::String(i.to_string())
+    } else {
+        Value::Number(i.into())
+    }
+}
+
+fn ui8_to_json(i: u64, options: &ConversionOptions) -> Value {
+    if options.bigint_for_i8 && i > MAX_SAFE_INTEGER as u64 {
+        Value::String(i.to_string())
+    } else {
+        Value::Number(i.into())
+    }
+}
 
 // 将WMI Variant转换为JSON Value
-fn variant_to_json(variant: &Variant) -> Value {
+pub(crate) fn variant_to_json(variant: &Variant, options: &ConversionOptions) -> Value {
     match variant {
         Variant::Empty => Value::Null,
         Variant::Null => Value::Null,
-        Variant::String(s) => Value::String(s.clone()),
+        Variant::String(s) => {
+            if options.parse_datetimes {
+                if let Some(iso) = parse_cim_datetime(s) {
+                    return Value::String(iso);
+                }
+            }
+            Value::String(s.clone())
+        }
         Variant::I1(i) => Value::Number((*i as i64).into()),
         Variant::I2(i) => Value::Number((*i as i64).into()),
         Variant::I4(i) => Value::Number((*i as i64).into()),
-        Variant::I8(i) => Value::Number((*i).into()),
+        Variant::I8(i) => i8_to_json(*i, options),
         Variant::UI1(i) => Value::Number((*i as u64).into()),
         Variant::UI2(i) => Value::Number((*i as u64).into()),
         Variant::UI4(i) => Value::Number((*i as u64).into()),
-        Variant::UI8(i) => Value::Number((*i).into()),
+        Variant::UI8(i) => ui8_to_json(*i, options),
         Variant::R4(f) => Value::Number(serde_json::Number::from_f64(*f as f64).unwrap_or_else(|| 0.into())),
         Variant::R8(f) => Value::Number(serde_json::Number::from_f64(*f).unwrap_or_else(|| 0.into())),
         Variant::Bool(b) => Value::Bool(*b),
         Variant::Array(arr) => {
-            let values: Vec<Value> = arr.iter().map(variant_to_json).collect();
+            let values: Vec<Value> = arr.iter().map(|v| variant_to_json(v, options)).collect();
             Value::Array(values)
         }
+        Variant::Object(obj) => obj
+            .path()
+            .ok()
+            .map(Value::String)
+            .unwrap_or_else(|| Value::String(format!("{:?}", variant))),
         _ => Value::String(format!("{:?}", variant)),
     }
 }
 
+// 将方法的输出参数对象(`IWbemClassWrapper`)展开成属性表，复用`variant_to_json`做值转换
+fn wrapper_to_json_map(
+    wrapper: &IWbemClassWrapper,
+    options: &ConversionOptions,
+) -> std::result::Result<HashMap<String, Value>, String> {
+    let mut map = HashMap::new();
+
+    for name in wrapper
+        .list_properties()
+        .map_err(|e| format!("读取属性列表失败: {}", e))?
+    {
+        let variant = wrapper
+            .get_property(&name)
+            .map_err(|e| format!("读取属性`{}`失败: {}", name, e))?;
+        map.insert(name, variant_to_json(&variant, options));
+    }
+
+    Ok(map)
+}
+
+// 将JSON值转换为WMI方法入参/实例属性可接受的Variant；WMI方法入参不支持嵌套对象
+fn json_to_variant(value: &Value) -> std::result::Result<Variant, String> {
+    match value {
+        Value::Null => Ok(Variant::Null),
+        Value::Bool(b) => Ok(Variant::Bool(*b)),
+        Value::String(s) => Ok(Variant::String(s.clone())),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Ok(Variant::I8(i))
+            } else if let Some(u) = n.as_u64() {
+                Ok(Variant::UI8(u))
+            } else if let Some(f) = n.as_f64() {
+                Ok(Variant::R8(f))
+            } else {
+                Err(format!("无法识别的数字: {}", n))
+            }
+        }
+        Value::Array(items) => {
+            let variants = items
+                .iter()
+                .map(json_to_variant)
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(Variant::Array(variants))
+        }
+        Value::Object(_) => Err("WMI方法入参/实例属性不支持嵌套JSON对象".to_string()),
+    }
+}
+
+// 将一组WMI查询结果行转换为JSON数组
+pub(crate) fn rows_to_json(
+    results: Vec<HashMap<String, Variant>>,
+    options: &ConversionOptions,
+) -> Vec<Value> {
+    results
+        .into_iter()
+        .map(|row| {
+            let json_row: HashMap<String, Value> = row
+                .into_iter()
+                .map(|(k, v)| (k, variant_to_json(&v, options)))
+                .collect();
+            Value::Object(json_row.into_iter().collect())
+        })
+        .collect()
+}
+
 // WMI客户端配置
 #[napi(object)]
 #[derive(Debug)]
 pub struct WmiClientConfig {
     pub namespace: Option<String>,
     pub timeout: Option<u32>,
+    /// 将CIM_DATETIME字符串属性（如`Win32_OperatingSystem.LastBootUpTime`）转换为ISO-8601字符串
+    pub parse_datetimes: Option<bool>,
+    /// 将超出JS安全整数范围的I8/UI8属性序列化为JSON字符串，避免精度丢失
+    pub bigint_for_i8: Option<bool>,
+    /// 远程主机名/地址，指定后连接`\\server\namespace`而不是本机
+    pub server: Option<String>,
+    /// 远程连接使用的用户名，本地连接不允许设置（WMI禁止为本机连接提供凭据）
+    pub username: Option<String>,
+    /// 远程连接使用的密码，与`username`配套使用
+    pub password: Option<String>,
+    /// 远程连接使用的域，留空表示使用`username`中的域或本地账户
+    pub domain: Option<String>,
+}
+
+/// 标识一条WMI连接的全部要素：命名空间、可选的远程主机与认证信息。
+/// `queryAsync`的连接池按该结构体整体做key，而不是只按命名空间，
+/// 否则两个指向不同远程主机、但命名空间字符串相同的客户端会错误地共享同一条连接。
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ConnectionParams {
+    pub namespace: String,
+    pub server: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub domain: Option<String>,
+}
+
+impl ConnectionParams {
+    fn from_config(config: Option<&WmiClientConfig>) -> Self {
+        ConnectionParams {
+            namespace: config
+                .and_then(|c| c.namespace.clone())
+                .unwrap_or_else(|| "root/cimv2".to_string()),
+            server: config.and_then(|c| c.server.clone()),
+            username: config.and_then(|c| c.username.clone()),
+            password: config.and_then(|c| c.password.clone()),
+            domain: config.and_then(|c| c.domain.clone()),
+        }
+    }
+
+    /// 本地连接禁止携带凭据信息（WMI本身的限制）
+    fn validate(&self) -> std::result::Result<(), String> {
+        if self.server.is_none()
+            && (self.username.is_some() || self.password.is_some() || self.domain.is_some())
+        {
+            return Err(
+                "本地WMI连接不能携带用户名/密码/域，WMI禁止为本机连接提供凭据".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 根据连接参数建立WMI连接：无`server`时连接本机命名空间，有`server`时连接
+/// `\\server\namespace`并附带可选凭据。供同步构造函数和[`connection_pool`]的
+/// 后台线程共用，保证两条路径用完全一致的方式解释同一组参数。
+///
+/// `wmi`没有单独的"授权信息"参数：`ConnectServer`的认证信息就是`username`/`password`/`domain`，
+/// 这里不额外发明一个`authority`字段。
+pub(crate) fn build_wmi_connection(
+    params: &ConnectionParams,
+) -> std::result::Result<WMIConnection, String> {
+    params.validate()?;
+
+    let namespace_path = params.namespace.replace('/', "\\");
+
+    match &params.server {
+        None => WMIConnection::with_namespace_path(&namespace_path)
+            .map_err(|e| format!("创建WMI连接失败: {}", e)),
+        Some(server) => WMIConnection::with_credentials_and_namespace(
+            server,
+            &namespace_path,
+            params.username.as_deref(),
+            params.password.as_deref(),
+            params.domain.as_deref(),
+        )
+        .map_err(|e| format!("创建远程WMI连接失败: {}", e)),
+    }
+}
+
+fn build_connection(config: Option<&WmiClientConfig>) -> Result<(WMIConnection, ConnectionParams)> {
+    let params = ConnectionParams::from_config(config);
+
+    let wmi_con =
+        build_wmi_connection(&params).map_err(|msg| Error::new(Status::GenericFailure, msg))?;
+
+    Ok((wmi_con, params))
 }
 
-// WMI客户端
+/// WMI客户端。与本地连接一样，远程/认证连接创建的`WMIConnection`同样是线程绑定的
+/// （COM单元绑定），不能跨线程共享；每个`WmiClient`实例只应在创建它的线程上调用
 #[napi]
 pub struct WmiClient {
     inner: WMIConnection,
-    namespace: String,
+    conn_params: ConnectionParams,
+    conversion: ConversionOptions,
 }
 
 #[napi]
 impl WmiClient {
-    /// 创建新的WMI客户端
+    /// 创建新的WMI客户端。默认连接本机`root/cimv2`；设置`config.server`后连接远程主机，
+    /// 此时可选地附带`username`/`password`/`domain`进行认证
     #[napi(constructor)]
     pub fn new(config: Option<WmiClientConfig>) -> Result<Self> {
-        let namespace = config
-            .as_ref()
-            .and_then(|c| c.namespace.clone())
-            .unwrap_or_else(|| "root/cimv2".to_string());
-
-        let com_lib = COMLibrary::new()
-            .map_err(|e| Error::new(Status::GenericFailure, format!("初始化COM失败: {}", e)))?;
-        
-        let wmi_con = WMIConnection::with_namespace_path(&namespace, com_lib)
-            .map_err(|e| Error::new(Status::GenericFailure, format!("创建WMI连接失败: {}", e)))?;
-
-        Ok(WmiClient { 
+        let (wmi_con, conn_params) = build_connection(config.as_ref())?;
+
+        let conversion = ConversionOptions {
+            parse_datetimes: config.as_ref().and_then(|c| c.parse_datetimes).unwrap_or(false),
+            bigint_for_i8: config.as_ref().and_then(|c| c.bigint_for_i8).unwrap_or(false),
+        };
+
+        Ok(WmiClient {
             inner: wmi_con,
-            namespace,
+            conn_params,
+            conversion,
         })
     }
 
@@ -73,25 +320,28 @@ impl WmiClient {
             .raw_query(&wql)
             .map_err(|e| Error::new(Status::GenericFailure, format!("查询失败: {}", e)))?;
 
-        let json_results: Vec<Value> = results
-            .into_iter()
-            .map(|row| {
-                let json_row: HashMap<String, Value> = row
-                    .into_iter()
-                    .map(|(k, v)| (k, variant_to_json(&v)))
-                    .collect();
-                Value::Object(json_row.into_iter().collect())
-            })
-            .collect();
-
-        serde_json::to_string(&json_results)
+        serde_json::to_string(&rows_to_json(results, &self.conversion))
             .map_err(|e| Error::new(Status::GenericFailure, format!("JSON序列化失败: {}", e)))
     }
 
+    /// 异步执行WQL查询，返回Promise<String>。查询在该连接参数（命名空间+远程主机+凭据）
+    /// 专属的后台COM线程上执行（见[`connection_pool`]），不会阻塞Node事件循环；
+    /// 连接参数不同的调用在各自的线程上并发执行，连接参数相同的调用在同一线程上依次执行。
+    /// 连接池按完整的连接参数分桶，而不是只按命名空间，
+    /// 避免两个指向不同主机/凭据的客户端在共享的命名空间字符串上互相串到对方的连接。
+    #[napi]
+    pub fn query_async(&self, wql: String) -> AsyncTask<connection_pool::QueryTask> {
+        AsyncTask::new(connection_pool::QueryTask {
+            params: self.conn_params.clone(),
+            wql,
+            conversion: self.conversion,
+        })
+    }
+
     /// 获取当前命名空间
     #[napi]
     pub fn get_namespace(&self) -> String {
-        self.namespace.clone()
+        self.conn_params.namespace.clone()
     }
 
     /// 测试连接是否正常
@@ -102,6 +352,235 @@ impl WmiClient {
             Err(_) => Ok(false),
         }
     }
+
+    /// 调用WMI方法（如`Win32_Process.Create`、`Win32_Service.StartService`）。
+    /// `object_path`是目标类或实例的路径，`params`是入参组成的JSON对象：先从方法定义
+    /// `spawn_instance`出一个入参对象，逐个属性`put_property`，再执行`exec_method`；
+    /// 返回值是出参对象展开后同样按`variant_to_json`序列化的JSON字符串
+    #[napi]
+    pub fn exec_method(&self, object_path: String, method: String, params: Value) -> Result<String> {
+        let method_def = self
+            .inner
+            .get_object(&object_path)
+            .and_then(|class| class.get_method(&method))
+            .map_err(|e| Error::new(Status::GenericFailure, format!("获取方法定义失败: {}", e)))?;
+
+        let in_params = match method_def {
+            Some(method_class) => {
+                let instance = method_class
+                    .spawn_instance()
+                    .map_err(|e| Error::new(Status::GenericFailure, format!("构造入参失败: {}", e)))?;
+
+                if let Some(props) = params.as_object() {
+                    for (key, value) in props {
+                        let variant = json_to_variant(value).map_err(|e| {
+                            Error::new(Status::InvalidArg, format!("参数`{}`无法转换为WMI类型: {}", key, e))
+                        })?;
+                        instance.put_property(key, variant).map_err(|e| {
+                            Error::new(Status::GenericFailure, format!("设置参数`{}`失败: {}", key, e))
+                        })?;
+                    }
+                }
+
+                Some(instance)
+            }
+            None => None,
+        };
+
+        let out = self
+            .inner
+            .exec_method(&object_path, &method, in_params.as_ref())
+            .map_err(|e| Error::new(Status::GenericFailure, format!("调用方法失败: {}", e)))?;
+
+        let json_row = match out {
+            Some(wrapper) => wrapper_to_json_map(&wrapper, &self.conversion)
+                .map_err(|e| Error::new(Status::GenericFailure, e))?,
+            None => HashMap::new(),
+        };
+
+        serde_json::to_string(&json_row)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("JSON序列化失败: {}", e)))
+    }
+
+    /// 创建或更新一个WMI实例，`properties`是属性组成的JSON对象，返回新/被更新实例的`__PATH`
+    #[napi]
+    pub fn put_instance(&self, class_name: String, properties: Value) -> Result<String> {
+        let class = self
+            .inner
+            .get_object(&class_name)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("获取类定义失败: {}", e)))?;
+
+        let instance = class
+            .spawn_instance()
+            .map_err(|e| Error::new(Status::GenericFailure, format!("创建实例失败: {}", e)))?;
+
+        let props = properties
+            .as_object()
+            .ok_or_else(|| Error::new(Status::InvalidArg, "properties必须是JSON对象".to_string()))?;
+
+        for (key, value) in props {
+            let variant = json_to_variant(value).map_err(|e| {
+                Error::new(Status::InvalidArg, format!("属性`{}`无法转换为WMI类型: {}", key, e))
+            })?;
+            instance
+                .put_property(key, variant)
+                .map_err(|e| Error::new(Status::GenericFailure, format!("设置属性`{}`失败: {}", key, e)))?;
+        }
+
+        self.inner
+            .put_instance(&instance)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("写入实例失败: {}", e)))?;
+
+        instance
+            .path()
+            .map_err(|e| Error::new(Status::GenericFailure, format!("写入成功但获取实例路径失败: {}", e)))
+    }
+
+    /// 删除`object_path`指向的WMI实例
+    #[napi]
+    pub fn delete_instance(&self, object_path: String) -> Result<()> {
+        self.inner
+            .delete_instance(&object_path)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("删除实例失败: {}", e)))
+    }
+
+    /// 订阅WMI事件通知查询（如 `SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE ...`），
+    /// 每当有新事件到达时通过回调返回JSON字符串。COM是单元绑定的，无法复用`inner`，
+    /// 因此订阅会在独立线程上创建自己的`WMIConnection`。
+    /// 返回的订阅句柄可调用 `stop()` 结束订阅的管理线程、不再触发回调；
+    /// 事件转发线程及其持有的连接要到阻塞中的事件读取返回后才会释放。
+    #[napi]
+    pub fn subscribe(
+        &self,
+        wql: String,
+        callback: ThreadsafeFunction<String, ErrorStrategy::CalleeHandled>,
+    ) -> Result<WmiSubscription> {
+        let conn_params = self.conn_params.clone();
+        let conversion = self.conversion;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker_stopped = stopped.clone();
+        let mut connected_once = false;
+
+        thread::spawn(move || loop {
+            if worker_stopped.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let wmi_con = match build_wmi_connection(&conn_params) {
+                Ok(con) => con,
+                // 订阅曾经建立过：这是重连失败（主机仍不可用），发出诊断事件并退避后继续重试
+                Err(e) if connected_once => {
+                    let diagnostic = serde_json::json!({ "__wmiReconnecting": true, "reason": e });
+                    callback.call(Ok(diagnostic.to_string()), ThreadsafeFunctionCallMode::NonBlocking);
+                    thread::sleep(SUBSCRIPTION_RECONNECT_BACKOFF);
+                    continue;
+                }
+                Err(e) => {
+                    callback.call(
+                        Err(Error::new(Status::GenericFailure, format!("创建WMI连接失败: {}", e))),
+                        ThreadsafeFunctionCallMode::NonBlocking,
+                    );
+                    return;
+                }
+            };
+
+            let events = match wmi_con.raw_notification::<HashMap<String, Variant>>(&wql) {
+                Ok(events) => events,
+                Err(e) => {
+                    callback.call(
+                        Err(Error::new(Status::GenericFailure, format!("创建事件订阅失败: {}", e))),
+                        ThreadsafeFunctionCallMode::NonBlocking,
+                    );
+                    return;
+                }
+            };
+            connected_once = true;
+
+            // `events`迭代器在没有新事件时会无限期阻塞（尤其是低频的设备/服务变更场景），
+            // 而`stop()`只能设置一个标志位，没办法打断一个已经阻塞在第三方COM调用里的线程。
+            // 因此把“消费events、把结果转发出来”这一步放到一个独立的转发线程里，
+            // 管理线程则通过`recv_timeout`按固定节奏醒来检查`stopped`，这样stop()之后
+            // 最多`SUBSCRIPTION_POLL_INTERVAL`就能让调用方不再收到回调，不需要等到下一个
+            // 事件或错误到达。注意：如果底层这次`next()`调用本身迟迟不返回，转发线程和它
+            // 持有的连接仍会在后台保留到下一个事件/错误出现为止，这是阻塞式COM调用在没有
+            // 原生取消能力时的已知限制。
+            let (event_tx, event_rx) = mpsc::channel();
+            thread::spawn(move || {
+                for event in events {
+                    if event_tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            });
+
+            loop {
+                if worker_stopped.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                let event = match event_rx.recv_timeout(SUBSCRIPTION_POLL_INTERVAL) {
+                    Ok(event) => event,
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+
+                match event {
+                    Ok(row) => {
+                        let json_row: HashMap<String, Value> = row
+                            .into_iter()
+                            .map(|(k, v)| (k, variant_to_json(&v, &conversion)))
+                            .collect();
+
+                        match serde_json::to_string(&json_row) {
+                            Ok(json) => {
+                                callback.call(Ok(json), ThreadsafeFunctionCallMode::NonBlocking);
+                            }
+                            Err(e) => {
+                                callback.call(
+                                    Err(Error::new(Status::GenericFailure, format!("事件JSON序列化失败: {}", e))),
+                                    ThreadsafeFunctionCallMode::NonBlocking,
+                                );
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let diagnostic = serde_json::json!({
+                            "__wmiReconnecting": true,
+                            "reason": e.to_string(),
+                        });
+                        callback.call(
+                            Ok(diagnostic.to_string()),
+                            ThreadsafeFunctionCallMode::NonBlocking,
+                        );
+                        break;
+                    }
+                }
+            }
+
+            // 连接出错或转发线程退出后，退避一段时间再重建连接，避免主机长时间下线时
+            // 对它和callback发起紧密的重试风暴
+            if !worker_stopped.load(Ordering::SeqCst) {
+                thread::sleep(SUBSCRIPTION_RECONNECT_BACKOFF);
+            }
+        });
+
+        Ok(WmiSubscription { stopped })
+    }
+}
+
+/// WMI事件订阅句柄，调用 `stop()` 结束订阅、不再触发回调
+#[napi]
+pub struct WmiSubscription {
+    stopped: Arc<AtomicBool>,
+}
+
+#[napi]
+impl WmiSubscription {
+    /// 停止订阅，通知后台线程结束接收循环
+    #[napi]
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
 }
 
 /// 快速查询函数，使用默认命名空间
@@ -110,8 +589,14 @@ pub fn quick_query(wql: String, namespace: Option<String>) -> Result<String> {
     let config = WmiClientConfig {
         namespace,
         timeout: None,
+        parse_datetimes: None,
+        bigint_for_i8: None,
+        server: None,
+        username: None,
+        password: None,
+        domain: None,
     };
-    
+
     let client = WmiClient::new(Some(config))?;
     client.query(wql)
 }
@@ -119,27 +604,167 @@ pub fn quick_query(wql: String, namespace: Option<String>) -> Result<String> {
 /// 获取当前系统的基本信息
 #[napi]
 pub fn get_system_info() -> Result<String> {
-    let com_lib = COMLibrary::new()
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to initialize COM: {}", e)))?;
-    
-    let wmi_con = WMIConnection::new(com_lib)
+    let wmi_con = WMIConnection::new()
         .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to create WMI connection: {}", e)))?;
 
     let results: Vec<HashMap<String, Variant>> = wmi_con
         .raw_query("SELECT * FROM Win32_ComputerSystem")
         .map_err(|e| Error::new(Status::GenericFailure, format!("Query failed: {}", e)))?;
 
-    let json_results: Vec<Value> = results
-        .into_iter()
-        .map(|row| {
-            let json_row: HashMap<String, Value> = row
-                .into_iter()
-                .map(|(k, v)| (k, variant_to_json(&v)))
-                .collect();
-            Value::Object(json_row.into_iter().collect())
-        })
-        .collect();
-
-    serde_json::to_string(&json_results)
+    serde_json::to_string(&rows_to_json(results, &ConversionOptions::default()))
         .map_err(|e| Error::new(Status::GenericFailure, format!("JSON serialization failed: {}", e)))
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cim_datetime_with_known_offset() {
+        assert_eq!(
+            parse_cim_datetime("20230615143012.123456+060"),
+            Some("2023-06-15T14:30:12.123456+01:00".to_string())
+        );
+        assert_eq!(
+            parse_cim_datetime("20230615143012.123456-300"),
+            Some("2023-06-15T14:30:12.123456-05:00".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_cim_datetime_with_unknown_offset() {
+        assert_eq!(
+            parse_cim_datetime("20230615143012.123456*000"),
+            Some("2023-06-15T14:30:12.123456".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_cim_datetime_rejects_non_matching_strings() {
+        assert_eq!(parse_cim_datetime("not a cim datetime"), None);
+        assert_eq!(parse_cim_datetime(""), None);
+        // 长度凑够25但第14字节不是'.'
+        assert_eq!(parse_cim_datetime("1234567890123456789012345"), None);
+    }
+
+    #[test]
+    fn parse_cim_datetime_does_not_panic_on_non_ascii() {
+        // 长度恰好25字节且第14字节是'.'，但包含多字节字符；此前会在字符边界之外
+        // 切片导致panic，现在必须安全地返回None
+        let s = "20230615143€.123456+060";
+        assert_eq!(s.len(), 25);
+        assert_eq!(s.as_bytes()[14], b'.');
+        assert_eq!(parse_cim_datetime(s), None);
+    }
+
+    #[test]
+    fn i8_to_json_keeps_safe_integers_as_numbers() {
+        let options = ConversionOptions {
+            parse_datetimes: false,
+            bigint_for_i8: true,
+        };
+        assert_eq!(i8_to_json(42, &options), Value::Number(42.into()));
+    }
+
+    #[test]
+    fn i8_to_json_stringifies_unsafe_integers_when_enabled() {
+        let options = ConversionOptions {
+            parse_datetimes: false,
+            bigint_for_i8: true,
+        };
+        let unsafe_value = MAX_SAFE_INTEGER + 1;
+        assert_eq!(
+            i8_to_json(unsafe_value, &options),
+            Value::String(unsafe_value.to_string())
+        );
+    }
+
+    #[test]
+    fn i8_to_json_keeps_unsafe_integers_as_numbers_when_disabled() {
+        let options = ConversionOptions {
+            parse_datetimes: false,
+            bigint_for_i8: false,
+        };
+        let unsafe_value = MAX_SAFE_INTEGER + 1;
+        assert_eq!(
+            i8_to_json(unsafe_value, &options),
+            Value::Number(unsafe_value.into())
+        );
+    }
+
+    #[test]
+    fn ui8_to_json_stringifies_unsafe_integers_when_enabled() {
+        let options = ConversionOptions {
+            parse_datetimes: false,
+            bigint_for_i8: true,
+        };
+        let unsafe_value = MAX_SAFE_INTEGER as u64 + 1;
+        assert_eq!(
+            ui8_to_json(unsafe_value, &options),
+            Value::String(unsafe_value.to_string())
+        );
+    }
+
+    #[test]
+    fn connection_params_rejects_credentials_without_server() {
+        let params = ConnectionParams {
+            namespace: "root/cimv2".to_string(),
+            server: None,
+            username: None,
+            password: None,
+            domain: Some("WORKGROUP".to_string()),
+        };
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn connection_params_allows_local_without_credentials() {
+        let params = ConnectionParams {
+            namespace: "root/cimv2".to_string(),
+            server: None,
+            username: None,
+            password: None,
+            domain: None,
+        };
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn connection_params_allows_remote_with_credentials() {
+        let params = ConnectionParams {
+            namespace: "root/cimv2".to_string(),
+            server: Some("192.168.1.1".to_string()),
+            username: Some("admin".to_string()),
+            password: Some("secret".to_string()),
+            domain: None,
+        };
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn json_to_variant_converts_scalars_and_arrays() {
+        assert_eq!(json_to_variant(&Value::Null), Ok(Variant::Null));
+        assert_eq!(json_to_variant(&Value::Bool(true)), Ok(Variant::Bool(true)));
+        assert_eq!(
+            json_to_variant(&Value::String("explorer.exe".to_string())),
+            Ok(Variant::String("explorer.exe".to_string()))
+        );
+        assert_eq!(
+            json_to_variant(&serde_json::json!(42)),
+            Ok(Variant::I8(42))
+        );
+        assert_eq!(
+            json_to_variant(&serde_json::json!([1, 2, 3])),
+            Ok(Variant::Array(vec![
+                Variant::I8(1),
+                Variant::I8(2),
+                Variant::I8(3)
+            ]))
+        );
+    }
+
+    #[test]
+    fn json_to_variant_rejects_nested_objects() {
+        assert!(json_to_variant(&serde_json::json!({ "nested": {} })).is_err());
+    }
+}
\ No newline at end of file